@@ -0,0 +1,25 @@
+/// Configures a [`MockServer`](crate::MockServer) before it starts listening.
+#[derive(Debug, Default, Clone)]
+pub struct MockServerBuilder {
+    max_request_body_size: Option<usize>,
+}
+
+impl MockServerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject any request whose body exceeds `max_request_body_size` bytes with a
+    /// `413 Payload Too Large`, instead of buffering it fully in memory.
+    ///
+    /// The accept loop passes this limit through to [`Request::from_hyper`](crate::Request::from_hyper),
+    /// which enforces it as the body is read off the wire.
+    pub fn max_request_body_size(mut self, max_request_body_size: usize) -> Self {
+        self.max_request_body_size = Some(max_request_body_size);
+        self
+    }
+
+    pub(crate) fn max_request_body_size_limit(&self) -> Option<usize> {
+        self.max_request_body_size
+    }
+}
@@ -1,7 +1,7 @@
-use std::{collections::HashMap, fmt};
+use std::{collections::HashMap, fmt, net::SocketAddr, str::FromStr};
 
 use http::{HeaderName, HeaderValue, Method};
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
 use url::Url;
 
 /// An incoming request to an instance of [`MockServer`].
@@ -28,6 +28,116 @@ pub struct Request {
     pub method: Method,
     pub headers: HashMap<HeaderName, Vec<HeaderValue>>,
     pub body: Vec<u8>,
+    /// The remote address of the TCP connection this request was read from.
+    ///
+    /// Set from the `peer_addr` argument passed into [`Request::from_hyper`]; `None` whenever a
+    /// `Request` is constructed without a live connection, e.g. via [`Request::from`] in a unit
+    /// test.
+    pub peer_addr: Option<SocketAddr>,
+}
+
+/// A JSON-friendly stand-in for [`Request`], used to (de)serialize captured traffic - e.g. for
+/// a record-and-replay workflow where requests observed by a [`MockServer`] are dumped to disk
+/// and later reloaded as fixtures.
+///
+/// `Request` can't derive `Serialize`/`Deserialize` directly: `Url`, `Method`, `HeaderName` and
+/// `HeaderValue` don't round-trip through serde, and header/body bytes aren't guaranteed to be
+/// valid UTF-8. `RequestSchema` works around this by going through a stable, all-`String` shape:
+/// - `headers` is a map of header name -> list of values; values that aren't valid UTF-8 are
+///   base64-encoded, with `is_base64` marking which ones need to be decoded back.
+/// - `body` is always base64-encoded, regardless of content.
+///
+/// [`MockServer`]: crate::MockServer
+#[derive(Serialize, Deserialize)]
+struct RequestSchema {
+    method: String,
+    url: String,
+    headers: HashMap<String, Vec<HeaderValueSchema>>,
+    body: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HeaderValueSchema {
+    value: String,
+    is_base64: bool,
+}
+
+impl HeaderValueSchema {
+    fn from_header_value(value: &HeaderValue) -> Self {
+        match std::str::from_utf8(value.as_bytes()) {
+            Ok(value) => HeaderValueSchema {
+                value: value.to_owned(),
+                is_base64: false,
+            },
+            Err(_) => HeaderValueSchema {
+                value: base64::encode(value.as_bytes()),
+                is_base64: true,
+            },
+        }
+    }
+
+    fn into_header_value(self) -> Result<HeaderValue, String> {
+        if self.is_base64 {
+            let bytes = base64::decode(&self.value)
+                .map_err(|e| format!("Invalid base64 header value: {}", e))?;
+            HeaderValue::from_bytes(&bytes).map_err(|e| format!("Invalid header value: {}", e))
+        } else {
+            HeaderValue::from_str(&self.value).map_err(|e| format!("Invalid header value: {}", e))
+        }
+    }
+}
+
+impl Serialize for Request {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut headers = HashMap::with_capacity(self.headers.len());
+        for (name, values) in &self.headers {
+            let values = values
+                .iter()
+                .map(HeaderValueSchema::from_header_value)
+                .collect();
+            headers.insert(name.as_str().to_owned(), values);
+        }
+
+        let schema = RequestSchema {
+            method: self.method.to_string(),
+            url: self.url.to_string(),
+            headers,
+            body: base64::encode(&self.body),
+        };
+        schema.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Request {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let schema = RequestSchema::deserialize(deserializer)?;
+
+        let method = Method::from_str(&schema.method).map_err(serde::de::Error::custom)?;
+        let url = Url::from_str(&schema.url).map_err(serde::de::Error::custom)?;
+
+        let mut headers = HashMap::with_capacity(schema.headers.len());
+        for (name, values) in schema.headers {
+            let name = HeaderName::from_str(&name).map_err(serde::de::Error::custom)?;
+            let values = values
+                .into_iter()
+                .map(HeaderValueSchema::into_header_value)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(serde::de::Error::custom)?;
+            headers.insert(name, values);
+        }
+
+        let body = base64::decode(&schema.body).map_err(serde::de::Error::custom)?;
+
+        Ok(Request {
+            url,
+            method,
+            headers,
+            body,
+            // Connection metadata isn't part of the recorded schema - it's meaningless once
+            // replayed from a fixture.
+            peer_addr: None,
+        })
+    }
 }
 
 impl fmt::Display for Request {
@@ -50,11 +160,99 @@ impl Request {
         serde_json::from_slice(&self.body)
     }
 
+    /// Parse the request's query string into a map of key-value pairs.
+    ///
+    /// If a key appears more than once, only the last value is kept - use [`query_params_multi`]
+    /// if you need every value for repeated keys.
+    ///
+    /// [`query_params_multi`]: Request::query_params_multi
+    pub fn query_params(&self) -> HashMap<String, String> {
+        self.url
+            .query_pairs()
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect()
+    }
+
+    /// Parse the request's query string into a map of keys to all the values they were given,
+    /// preserving repeated keys (e.g. `?tag=a&tag=b`).
+    pub fn query_params_multi(&self) -> HashMap<String, Vec<String>> {
+        let mut params: HashMap<String, Vec<String>> = HashMap::new();
+        for (key, value) in self.url.query_pairs() {
+            params
+                .entry(key.into_owned())
+                .or_default()
+                .push(value.into_owned());
+        }
+        params
+    }
+
+    /// Deserialize the request's query string into `T`, mirroring [`body_json`].
+    ///
+    /// [`body_json`]: Request::body_json
+    pub fn query<T: DeserializeOwned>(&self) -> Result<T, serde_urlencoded::de::Error> {
+        serde_urlencoded::from_str(self.url.query().unwrap_or(""))
+    }
+
+    /// Deserialize an `application/x-www-form-urlencoded` request body into `T`.
+    pub fn body_form<T: DeserializeOwned>(&self) -> Result<T, serde_urlencoded::de::Error> {
+        serde_urlencoded::from_bytes(&self.body)
+    }
+
+    /// Parse a `multipart/form-data` body into its individual [`Part`]s, using the boundary
+    /// declared in the `Content-Type` header.
+    pub fn body_multipart(&self) -> Result<Vec<Part>, MultipartError> {
+        let content_type = self
+            .headers
+            .get(&http::header::CONTENT_TYPE)
+            .and_then(|values| values.first())
+            .and_then(|value| value.to_str().ok())
+            .ok_or(MultipartError::MissingContentType)?;
+
+        if !content_type.starts_with("multipart/form-data") {
+            return Err(MultipartError::NotMultipart);
+        }
+
+        let boundary = content_type
+            .split(';')
+            .map(str::trim)
+            .find_map(|segment| segment.strip_prefix("boundary="))
+            .map(|boundary| boundary.trim_matches('"'))
+            .ok_or(MultipartError::MissingBoundary)?;
+
+        let delimiter = format!("--{}", boundary).into_bytes();
+        let segments = split_on_boundary(&self.body, &delimiter);
+
+        let mut parts = Vec::new();
+        // The first segment is the preamble before the first boundary; the last is the
+        // closing `--boundary--` marker (and anything after it) - both are skipped below.
+        for segment in segments.iter().skip(1) {
+            let segment = segment.strip_prefix(b"\r\n".as_slice()).unwrap_or(segment);
+            if segment.is_empty() || segment.starts_with(b"--") {
+                continue;
+            }
+            parts.push(Part::parse(segment)?);
+        }
+
+        Ok(parts)
+    }
+
     pub async fn from(request: http::Request<hyper::Body>) -> Request {
-        Self::from_hyper(request).await
+        Self::from_hyper(request, None, None)
+            .await
+            .expect("from_hyper cannot fail when no max_body_size is set")
     }
 
-    pub(crate) async fn from_hyper(request: hyper::Request<hyper::Body>) -> Request {
+    /// Builds a `Request` from a hyper request, reading at most `max_body_size` bytes of body.
+    ///
+    /// Returns `Err` as soon as the body is known to exceed `max_body_size` - via its
+    /// `Content-Length` header, or, failing that, the running total of bytes read so far -
+    /// without buffering the remainder. Callers turn that into a `413 Payload Too Large`
+    /// response rather than a `Request`.
+    pub(crate) async fn from_hyper(
+        request: hyper::Request<hyper::Body>,
+        peer_addr: Option<SocketAddr>,
+        max_body_size: Option<usize>,
+    ) -> Result<Request, MaxBodySizeExceeded> {
         let (parts, body) = request.into_parts();
         let method = parts.method;
         let url = match parts.uri.authority() {
@@ -74,16 +272,376 @@ impl Request {
                 .or_insert_with(|| vec![value.clone()]);
         }
 
-        let body = hyper::body::to_bytes(body)
-            .await
-            .expect("Failed to read request body.")
-            .to_vec();
+        if let Some(max_body_size) = max_body_size {
+            let content_length = parts
+                .headers
+                .get(http::header::CONTENT_LENGTH)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<usize>().ok());
+            if matches!(content_length, Some(content_length) if content_length > max_body_size) {
+                return Err(MaxBodySizeExceeded {
+                    limit: max_body_size,
+                });
+            }
+        }
 
-        Self {
+        let body = match max_body_size {
+            Some(max_body_size) => {
+                use hyper::body::HttpBody;
+
+                let mut body = body;
+                let mut bytes = Vec::new();
+                while let Some(chunk) = body.data().await {
+                    let chunk = chunk.expect("Failed to read request body.");
+                    bytes.extend_from_slice(&chunk);
+                    if bytes.len() > max_body_size {
+                        return Err(MaxBodySizeExceeded {
+                            limit: max_body_size,
+                        });
+                    }
+                }
+                bytes
+            }
+            None => hyper::body::to_bytes(body)
+                .await
+                .expect("Failed to read request body.")
+                .to_vec(),
+        };
+
+        Ok(Self {
             url,
             method,
             headers,
             body,
+            peer_addr,
+        })
+    }
+}
+
+/// Returned by [`Request::from_hyper`] when the incoming body crosses the configured maximum
+/// size. The mock server should respond with `413 Payload Too Large` without fully buffering
+/// the request - set via `MockServerBuilder::max_request_body_size`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxBodySizeExceeded {
+    pub limit: usize,
+}
+
+impl fmt::Display for MaxBodySizeExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the request body exceeded the maximum allowed size of {} bytes",
+            self.limit
+        )
+    }
+}
+
+impl std::error::Error for MaxBodySizeExceeded {}
+
+/// A single part of a `multipart/form-data` body, as parsed by [`Request::body_multipart`].
+///
+/// Pairing this with a `BodyPartExactMatcher` (asserting a named part is present with expected
+/// contents) is left to the matchers module - not part of this crate slice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    /// The part's `name`, from its `Content-Disposition: form-data` header.
+    pub name: String,
+    /// The part's `filename`, when present (file uploads, as opposed to plain form fields).
+    pub file_name: Option<String>,
+    pub headers: HashMap<HeaderName, Vec<HeaderValue>>,
+    pub body: Vec<u8>,
+}
+
+impl Part {
+    fn parse(segment: &[u8]) -> Result<Part, MultipartError> {
+        let header_end = find_bytes(segment, b"\r\n\r\n").ok_or(MultipartError::MalformedPart)?;
+        let header_block = &segment[..header_end];
+        // The trailing `\r\n` that precedes the next `--boundary` is already excluded by
+        // `split_on_boundary`, so whatever's left here is part of the body itself.
+        let body = &segment[header_end + 4..];
+
+        let mut headers: HashMap<HeaderName, Vec<HeaderValue>> = HashMap::new();
+        let mut name = None;
+        let mut file_name = None;
+        for line in header_block.split(|&b| b == b'\n') {
+            let line = line.strip_suffix(b"\r".as_slice()).unwrap_or(line);
+            if line.is_empty() {
+                continue;
+            }
+            let line = std::str::from_utf8(line).map_err(|_| MultipartError::MalformedPart)?;
+            let (header_name, header_value) =
+                line.split_once(':').ok_or(MultipartError::MalformedPart)?;
+            let header_name = header_name.trim();
+            let header_value = header_value.trim();
+
+            if header_name.eq_ignore_ascii_case("content-disposition") {
+                for directive in header_value.split(';').map(str::trim) {
+                    if let Some(value) = directive.strip_prefix("name=") {
+                        name = Some(value.trim_matches('"').to_owned());
+                    } else if let Some(value) = directive.strip_prefix("filename=") {
+                        file_name = Some(value.trim_matches('"').to_owned());
+                    }
+                }
+            }
+
+            let header_name =
+                HeaderName::from_str(header_name).map_err(|_| MultipartError::MalformedPart)?;
+            let header_value =
+                HeaderValue::from_str(header_value).map_err(|_| MultipartError::MalformedPart)?;
+            headers.entry(header_name).or_default().push(header_value);
+        }
+
+        Ok(Part {
+            name: name.ok_or(MultipartError::MalformedPart)?,
+            file_name,
+            headers,
+            body: body.to_vec(),
+        })
+    }
+}
+
+/// Returned by [`Request::body_multipart`] when the body isn't well-formed `multipart/form-data`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultipartError {
+    /// The request has no `Content-Type` header.
+    MissingContentType,
+    /// The `Content-Type` header isn't `multipart/form-data`.
+    NotMultipart,
+    /// The `Content-Type` header has no `boundary` parameter.
+    MissingBoundary,
+    /// A part's headers or `Content-Disposition` couldn't be parsed.
+    MalformedPart,
+}
+
+impl fmt::Display for MultipartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            MultipartError::MissingContentType => "missing Content-Type header",
+            MultipartError::NotMultipart => "Content-Type is not multipart/form-data",
+            MultipartError::MissingBoundary => "Content-Type has no boundary parameter",
+            MultipartError::MalformedPart => "a multipart part could not be parsed",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for MultipartError {}
+
+/// Splits `haystack` on every occurrence of `boundary`, anchoring each match to a line start
+/// (i.e. requiring it be preceded by `\r\n`, or be at offset `0`) per RFC 2046. Without this
+/// anchor, binary part content that happens to contain the literal boundary bytes would be
+/// mistaken for a delimiter and corrupt that part's body.
+fn split_on_boundary<'a>(haystack: &'a [u8], boundary: &[u8]) -> Vec<&'a [u8]> {
+    let anchored = [b"\r\n".as_slice(), boundary].concat();
+
+    let mut matches = Vec::new();
+    if haystack.starts_with(boundary) {
+        matches.push((0, boundary.len()));
+    }
+    let mut i = 0;
+    while i + anchored.len() <= haystack.len() {
+        if haystack[i..i + anchored.len()] == anchored[..] {
+            matches.push((i, anchored.len()));
+            i += anchored.len();
+        } else {
+            i += 1;
+        }
+    }
+
+    let mut result = Vec::with_capacity(matches.len() + 1);
+    let mut start = 0;
+    for (pos, len) in matches {
+        result.push(&haystack[start..pos]);
+        start = pos + len;
+    }
+    result.push(&haystack[start..]);
+    result
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(
+        method: Method,
+        url: &str,
+        headers: HashMap<HeaderName, Vec<HeaderValue>>,
+        body: Vec<u8>,
+    ) -> Request {
+        Request {
+            url: Url::parse(url).unwrap(),
+            method,
+            headers,
+            body,
+            peer_addr: None,
         }
     }
+
+    #[test]
+    fn request_serde_round_trips_a_non_utf8_header_value() {
+        let mut headers = HashMap::new();
+        headers.insert(
+            HeaderName::from_static("x-binary"),
+            vec![HeaderValue::from_bytes(&[0xff, 0xfe, 0xcf]).unwrap()],
+        );
+        let original = request(
+            Method::GET,
+            "http://localhost/path?a=1",
+            headers,
+            b"hello".to_vec(),
+        );
+
+        let json = serde_json::to_string(&original).unwrap();
+        let round_tripped: Request = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(round_tripped.method, original.method);
+        assert_eq!(round_tripped.url, original.url);
+        assert_eq!(round_tripped.body, original.body);
+        assert_eq!(
+            round_tripped
+                .headers
+                .get(&HeaderName::from_static("x-binary")),
+            original.headers.get(&HeaderName::from_static("x-binary"))
+        );
+    }
+
+    #[test]
+    fn query_params_multi_preserves_repeated_keys() {
+        let req = request(
+            Method::GET,
+            "http://localhost/path?tag=a&tag=b&name=joe",
+            HashMap::new(),
+            Vec::new(),
+        );
+
+        assert_eq!(
+            req.query_params_multi().get("tag"),
+            Some(&vec!["a".to_owned(), "b".to_owned()])
+        );
+        // The single-value helper keeps the last occurrence of a repeated key.
+        assert_eq!(req.query_params().get("tag"), Some(&"b".to_owned()));
+        assert_eq!(req.query_params().get("name"), Some(&"joe".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn from_hyper_populates_peer_addr_from_the_connection() {
+        let hyper_request = http::Request::builder()
+            .method("GET")
+            .uri("/")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let peer_addr: SocketAddr = "127.0.0.1:4242".parse().unwrap();
+
+        let request = Request::from_hyper(hyper_request, Some(peer_addr), None)
+            .await
+            .unwrap();
+
+        assert_eq!(request.peer_addr, Some(peer_addr));
+    }
+
+    #[tokio::test]
+    async fn from_hyper_rejects_an_oversized_body_via_content_length() {
+        let hyper_request = http::Request::builder()
+            .method("POST")
+            .uri("/upload")
+            .header(http::header::CONTENT_LENGTH, "10")
+            .body(hyper::Body::from(vec![0u8; 10]))
+            .unwrap();
+
+        let result = Request::from_hyper(hyper_request, None, Some(5)).await;
+
+        assert_eq!(result.unwrap_err(), MaxBodySizeExceeded { limit: 5 });
+    }
+
+    #[tokio::test]
+    async fn from_hyper_rejects_an_oversized_body_via_streaming_read() {
+        // No `Content-Length` header, so the limit can only be enforced as chunks stream in.
+        let chunks: Vec<Result<hyper::body::Bytes, std::io::Error>> = vec![
+            Ok(hyper::body::Bytes::from_static(b"hello ")),
+            Ok(hyper::body::Bytes::from_static(b"world")),
+        ];
+        let body = hyper::Body::wrap_stream(futures_util::stream::iter(chunks));
+        let hyper_request = http::Request::builder()
+            .method("POST")
+            .uri("/upload")
+            .body(body)
+            .unwrap();
+
+        let result = Request::from_hyper(hyper_request, None, Some(5)).await;
+
+        assert_eq!(result.unwrap_err(), MaxBodySizeExceeded { limit: 5 });
+    }
+
+    #[test]
+    fn body_multipart_parses_parts_and_ignores_boundary_like_bytes_in_content() {
+        let boundary = "XBOUNDARY";
+        let mut body = Vec::new();
+        body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        body.extend_from_slice(b"Content-Disposition: form-data; name=\"field\"\r\n\r\n");
+        body.extend_from_slice(b"plain value\r\n");
+        body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        body.extend_from_slice(
+            b"Content-Disposition: form-data; name=\"file\"; filename=\"payload.bin\"\r\n\r\n",
+        );
+        // The file's raw bytes contain the boundary marker without a preceding CRLF - this must
+        // not be mistaken for a delimiter and split the part in two.
+        let file_content = format!("prefix--{}-suffix", boundary).into_bytes();
+        body.extend_from_slice(&file_content);
+        body.extend_from_slice(b"\r\n");
+        body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+
+        let mut headers = HashMap::new();
+        headers.insert(
+            http::header::CONTENT_TYPE,
+            vec![
+                HeaderValue::from_str(&format!("multipart/form-data; boundary={}", boundary))
+                    .unwrap(),
+            ],
+        );
+        let req = request(Method::POST, "http://localhost/upload", headers, body);
+
+        let parts = req.body_multipart().unwrap();
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].name, "field");
+        assert_eq!(parts[0].file_name, None);
+        assert_eq!(parts[0].body, b"plain value");
+        assert_eq!(parts[1].name, "file");
+        assert_eq!(parts[1].file_name.as_deref(), Some("payload.bin"));
+        assert_eq!(parts[1].body, file_content);
+    }
+
+    #[test]
+    fn body_multipart_keeps_a_trailing_crlf_that_belongs_to_the_part_content() {
+        let boundary = "XBOUNDARY";
+        let mut body = Vec::new();
+        body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        body.extend_from_slice(b"Content-Disposition: form-data; name=\"field\"\r\n\r\n");
+        // The part's own content ends in a CRLF; the delimiter contributes a second, separate
+        // CRLF right before `--boundary` per RFC 2046. Only that second one is framing - the
+        // first belongs to the content and must survive.
+        body.extend_from_slice(b"hello\r\n");
+        body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+        let mut headers = HashMap::new();
+        headers.insert(
+            http::header::CONTENT_TYPE,
+            vec![
+                HeaderValue::from_str(&format!("multipart/form-data; boundary={}", boundary))
+                    .unwrap(),
+            ],
+        );
+        let req = request(Method::POST, "http://localhost/upload", headers, body);
+
+        let parts = req.body_multipart().unwrap();
+
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].body, b"hello\r\n");
+    }
 }